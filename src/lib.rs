@@ -1,9 +1,179 @@
+/// A parsed `cfg`-like expression: a small boolean algebra over `cfg` atoms.
+///
+/// This is what `cfg_aliases!`, `cfg_eval!`, and `cfg_aliases_with!` build out of an
+/// alias's `{ ... }` body before evaluating it, following the same shape as
+/// rust-analyzer's own `cfg` crate (`Atom`/`KeyValue`/`All`/`Any`/`Not`). Splitting
+/// the boolean combinators out of the env-var lookups like this is what makes
+/// [`CfgExpr::fold`] pluggable: the combinators don't care where a leaf's truth
+/// value came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CfgExpr {
+    /// A bare identifier, e.g. `unix`.
+    Atom(String),
+    /// A `key = "value"` pair, e.g. `target_os = "linux"` or `feature = "foo"`.
+    KeyValue(String, String),
+    /// `all(...)`: true when every inner expression is true.
+    All(Vec<CfgExpr>),
+    /// `any(...)`: true when at least one inner expression is true.
+    Any(Vec<CfgExpr>),
+    /// `not(...)`: the negation of the inner expression.
+    Not(Box<CfgExpr>),
+}
+
+impl CfgExpr {
+    /// Evaluate this expression, asking `query` for the truth of every leaf atom.
+    ///
+    /// `query` is called as `query(key, value)` for each leaf: `value` is `None` for
+    /// a bare identifier like `unix` and `Some(v)` for a `key = "v"` pair. Returns
+    /// `None` if the expression itself can't be reduced to a definite answer (an
+    /// empty `all(...)`/`any(...)` still reduces fine; this is kept `Option<bool>`,
+    /// rather than `bool`, so expression shapes added later that don't always have
+    /// an answer can say so without changing this signature).
+    pub fn fold(&self, query: &dyn Fn(&str, Option<&str>) -> bool) -> Option<bool> {
+        match self {
+            CfgExpr::Atom(name) => Some(query(name, None)),
+            CfgExpr::KeyValue(key, value) => Some(query(key, Some(value))),
+            CfgExpr::All(exprs) => exprs.iter().try_fold(true, |acc, e| Some(acc && e.fold(query)?)),
+            CfgExpr::Any(exprs) => exprs.iter().try_fold(false, |acc, e| Some(acc || e.fold(query)?)),
+            CfgExpr::Not(e) => e.fold(query).map(|b| !b),
+        }
+    }
+}
+
+/// Implementation details used by the `cfg_aliases!`/`cfg_eval!`/`cfg_aliases_with!`
+/// macros. Not part of the public API; the only stable entry points are the macros
+/// themselves and [`CfgExpr`].
+#[doc(hidden)]
+pub mod __private {
+    /// Checks whether the CFG environment variable for a bare identifier is set.
+    pub fn cfg_is_set(name: &str) -> bool {
+        std::env::var(format!(
+            "CARGO_CFG_{}",
+            name.to_uppercase().replace('-', "_")
+        ))
+        .is_ok()
+    }
+
+    /// Checks for the presence of a feature.
+    pub fn cfg_has_feature(feature: &str) -> bool {
+        std::env::var(format!(
+            "CARGO_FEATURE_{}",
+            feature.to_uppercase().replace('-', "_").replace('"', "")
+        ))
+        .map(|x| x == "1")
+        .unwrap_or(false)
+    }
+
+    /// Checks whether a CFG environment variable contains the given value.
+    pub fn cfg_contains(name: &str, value: &str) -> bool {
+        std::env::var(format!(
+            "CARGO_CFG_{}",
+            name.to_uppercase().replace('-', "_")
+        ))
+        .unwrap_or_default()
+        .split(',')
+        .any(|x| x == value)
+    }
+
+    /// Checks the active rustc's version against a requested one, e.g. `1.65` or
+    /// `1.65.0`. Shells out to the compiler named by the `RUSTC` env var (falling
+    /// back to plain `"rustc"`) once per build, parsing the `release: X.Y.Z` line
+    /// out of `rustc -V --verbose`, and caches the result so repeated `version(...)`
+    /// predicates in the same build script don't re-invoke rustc.
+    pub fn cfg_version_at_least(requested: &str) -> bool {
+        static RUSTC_VERSION: std::sync::OnceLock<(u32, u32, u32)> = std::sync::OnceLock::new();
+        let rustc_version = *RUSTC_VERSION.get_or_init(|| {
+            std::env::var("RUSTC")
+                .ok()
+                .or_else(|| Some("rustc".to_string()))
+                .and_then(|rustc| {
+                    std::process::Command::new(rustc)
+                        .args(["-V", "--verbose"])
+                        .output()
+                        .ok()
+                })
+                .and_then(|output| String::from_utf8(output.stdout).ok())
+                .and_then(|output| {
+                    output
+                        .lines()
+                        .find_map(|line| line.strip_prefix("release: ").map(str::to_string))
+                })
+                .and_then(|release| {
+                    let release = release.split(['-']).next().unwrap_or(&release).to_string();
+                    let mut parts = release.split('.');
+                    let major: u32 = parts.next()?.parse().ok()?;
+                    let minor: u32 = parts.next()?.parse().ok()?;
+                    let patch: u32 = parts.next().unwrap_or("0").parse().ok()?;
+                    Some((major, minor, patch))
+                })
+                .unwrap_or((0, 0, 0))
+        });
+
+        let requested = requested.split(['-']).next().unwrap_or(requested);
+        let mut parts = requested.split('.');
+        let major: u32 = parts.next().and_then(|x| x.parse().ok()).unwrap_or(0);
+        let minor: u32 = parts.next().and_then(|x| x.parse().ok()).unwrap_or(0);
+        let patch: u32 = parts.next().and_then(|x| x.parse().ok()).unwrap_or(0);
+
+        rustc_version >= (major, minor, patch)
+    }
+
+    /// Detects whether the active Cargo understands the namespaced `cargo::` build
+    /// script directive syntax (stabilized in Cargo 1.77), which is required for
+    /// `cargo::rustc-check-cfg` to be accepted instead of rejected. Older Cargo only
+    /// understands the single-colon `cargo:KEY=VALUE` form, so we shell out to the
+    /// Cargo named by the `CARGO` env var (falling back to plain `"cargo"`) and
+    /// parse its `-V` output. The result is cached so repeated alias declarations in
+    /// the same build script don't pay for the subprocess twice.
+    pub fn cargo_supports_check_cfg() -> bool {
+        static SUPPORTS_CHECK_CFG: std::sync::OnceLock<bool> = std::sync::OnceLock::new();
+        *SUPPORTS_CHECK_CFG.get_or_init(|| {
+            std::env::var("CARGO")
+                .ok()
+                .or_else(|| Some("cargo".to_string()))
+                .and_then(|cargo| std::process::Command::new(cargo).arg("-V").output().ok())
+                .and_then(|output| String::from_utf8(output.stdout).ok())
+                .and_then(|version| {
+                    let version = version.strip_prefix("cargo ")?.trim().to_string();
+                    let mut parts = version.split('.');
+                    let major: u32 = parts.next()?.parse().ok()?;
+                    let minor: u32 = parts.next()?.parse().ok()?;
+                    Some((major, minor) >= (1, 77))
+                })
+                .unwrap_or(false)
+        })
+    }
+
+    /// The query `cfg_aliases!`/`cfg_eval!` fold their [`crate::CfgExpr`] against:
+    /// today's hard-wired `CARGO_CFG_*`/`CARGO_FEATURE_*` env reads, plus the
+    /// `version(...)` rustc check. `cfg_aliases_with!` swaps this out for a
+    /// caller-provided query instead.
+    pub fn default_query(key: &str, value: Option<&str>) -> bool {
+        match (key, value) {
+            ("feature", Some(v)) => cfg_has_feature(v),
+            ("version", Some(v)) => cfg_version_at_least(v),
+            (key, Some(v)) => cfg_contains(key, v),
+            (key, None) => cfg_is_set(key),
+        }
+    }
+
+    /// Picks the value of the first `(condition, value)` pair whose condition is
+    /// `true`, mirroring the first-match cascade `cfg_aliases_match!` builds from
+    /// its arms (the `_` arm is represented as an unconditional `true`). Exposed
+    /// as a plain function, rather than only reachable through the
+    /// `println!`-emitting macro, so the exclusivity semantics are testable
+    /// directly.
+    pub fn first_match<T: Copy>(arms: &[(bool, T)]) -> Option<T> {
+        arms.iter().find(|(cond, _)| *cond).map(|(_, value)| *value)
+    }
+}
+
 /// Parse cfg aliases and output cargo cfg aliases
 ///
 /// As an example:
 ///
 /// ```rust
-/// # use cfg_aliases::cfg_aliases
+/// # use cfg_aliases::cfg_aliases;
 ///
 /// // Setup cfg aliases
 /// cfg_aliases! {
@@ -32,6 +202,25 @@
 /// println!("We're in dummy mode, specify another feature if you want a smarter app!");
 /// ```
 ///
+/// Besides bare identifiers, `all`/`any`/`not`, and `key = "value"` pairs, an alias's
+/// `{ ... }` body can also use two predicates borrowed from newer rustc `cfg` syntax:
+///
+/// ```rust
+/// # use cfg_aliases::cfg_aliases;
+///
+/// cfg_aliases! {
+///     // `version("x.y.z")`: true when the active rustc is at least that version.
+///     has_let_else: { version("1.65") },
+///     // `target(key = value, ...)`: sugar for `all(target_key = value, ...)`.
+///     linux_x86: { target(os = "linux", arch = "x86_64") },
+/// }
+/// ```
+///
+/// Every alias declared this way is also registered with Cargo via
+/// `cargo::rustc-check-cfg`, so `#[cfg(some_alias)]` in downstream code doesn't trip
+/// the `unexpected_cfgs` lint. This registration is skipped automatically on older
+/// Cargo versions that don't understand the `cargo::` directive syntax.
+///
 /// This greatly improves what would otherwise look like this without the aliases:
 ///
 /// ```rust
@@ -64,57 +253,27 @@
 /// [sm]: https://users.rust-lang.org/t/any-such-thing-as-cfg-aliases/40100/3
 #[macro_export]
 macro_rules! cfg_aliases {
-    // Helper that just checks whether the CFG environment variable is set
-    (@cfg_is_set $cfgname:ident) => {
-        std::env::var(
-            format!(
-                "CARGO_CFG_{}",
-                &stringify!($cfgname).to_uppercase().replace("-", "_")
-            )
-        ).is_ok()
-    };
-    // Helper to check for the presense of a feature
-    (@cfg_has_feature $feature:expr) => {
-        {
-            std::env::var(
-                format!(
-                    "CARGO_FEATURE_{}",
-                    &stringify!($feature).to_uppercase().replace("-", "_").replace('"', "")
-                )
-            ).map(|x| x == "1").unwrap_or(false)
-        }
-    };
-
-    // Helper that checks whether a CFG environment contains the given value
-    (@cfg_contains $cfgname:ident = $cfgvalue:expr) => {
-        std::env::var(
-            format!(
-                "CARGO_CFG_{}",
-                &stringify!($cfgname).to_uppercase().replace("-", "_")
-            )
-        ).unwrap_or("".to_string()).split(",").find(|x| x == &$cfgvalue).is_some()
-    };
-
-    // Emitting `any(clause1,clause2,...)`: convert to `$crate::cfg_aliases!(clause1) && $crate::cfg_aliases!(clause2) && ...`
+    // Emitting `all(clause1,clause2,...)`: fold the grouped sub-expressions into a
+    // `CfgExpr::All`.
     (
-        @parser_emit
+        @expr_emit
         all
         $({$($grouped:tt)+})+
     ) => {
-        ($(
-            ($crate::cfg_aliases!(@parser $($grouped)+))
-        )&&+)
+        $crate::CfgExpr::All(vec![
+            $( $crate::cfg_aliases!(@expr $($grouped)+), )+
+        ])
     };
 
-    // Likewise for `all(clause1,clause2,...)`.
+    // Likewise for `any(clause1,clause2,...)`.
     (
-        @parser_emit
+        @expr_emit
         any
         $({$($grouped:tt)+})+
     ) => {
-        ($(
-            ($crate::cfg_aliases!(@parser $($grouped)+))
-        )||+)
+        $crate::CfgExpr::Any(vec![
+            $( $crate::cfg_aliases!(@expr $($grouped)+), )+
+        ])
     };
 
     // "@clause" rules are used to parse the comma-separated lists. They munch
@@ -137,13 +296,13 @@ macro_rules! cfg_aliases {
     // delimeters so that the grouping can be easily extracted again in the
     // emission stage.
     (
-        @parser_clause
+        @expr_clause
         $op:ident
         [$({$($grouped:tt)+})*]
         [, $($rest:tt)*]
         $($current:tt)+
     ) => {
-        $crate::cfg_aliases!(@parser_clause $op [
+        $crate::cfg_aliases!(@expr_clause $op [
             $(
                 {$($grouped)+}
             )*
@@ -157,13 +316,13 @@ macro_rules! cfg_aliases {
     // comma. In this case, we add that token to the list of tokens in the
     // current clause, then move on to the next one.
     (
-        @parser_clause
+        @expr_clause
         $op:ident
         [$({$($grouped:tt)+})*]
         [$tok:tt $($rest:tt)*]
         $($current:tt)*
     ) => {
-        $crate::cfg_aliases!(@parser_clause $op [
+        $crate::cfg_aliases!(@expr_clause $op [
             $(
                 {$($grouped)+}
             )*
@@ -176,13 +335,13 @@ macro_rules! cfg_aliases {
     // finish off the "current" token group, then delegate to the emission
     // rule.
     (
-        @parser_clause
+        @expr_clause
         $op:ident
         [$({$($grouped:tt)+})*]
         []
         $($current:tt)+
     ) => {
-        $crate::cfg_aliases!(@parser_emit $op
+        $crate::cfg_aliases!(@expr_emit $op
             $(
                 {$($grouped)+}
             )*
@@ -192,68 +351,308 @@ macro_rules! cfg_aliases {
 
 
     // `all(clause1, clause2...)` : we must parse this comma-separated list and
-    // partner with `@emit all` to output a bunch of && terms.
+    // partner with `@expr_emit all` to build a `CfgExpr::All`.
     (
-        @parser
+        @expr
         all($($tokens:tt)+)
     ) => {
-        $crate::cfg_aliases!(@parser_clause all [] [$($tokens)+])
+        $crate::cfg_aliases!(@expr_clause all [] [$($tokens)+])
     };
 
     // Likewise for `any(clause1, clause2...)`
     (
-        @parser
+        @expr
         any($($tokens:tt)+)
     ) => {
-        $crate::cfg_aliases!(@parser_clause any [] [$($tokens)+])
+        $crate::cfg_aliases!(@expr_clause any [] [$($tokens)+])
     };
 
-    // `not(clause)`: compute the inner clause, then just negate it.
+    // `not(clause)`: build the inner expression, then wrap it in a negation.
     (
-        @parser
+        @expr
         not($($tokens:tt)+)
     ) => {
-        !($crate::cfg_aliases!(@parser $($tokens)+))
+        $crate::CfgExpr::Not(Box::new($crate::cfg_aliases!(@expr $($tokens)+)))
+    };
+
+    // `version("x.y.z")`: test the active rustc's version against the given one.
+    (
+        @expr
+        version($ver:expr)
+    ) => {
+        $crate::CfgExpr::KeyValue("version".to_string(), ($ver).to_string())
+    };
+
+    // `target(key = value, ...)`: sugar for `all(target_key = value, ...)`, mirroring
+    // the compact `cfg(target(...))` form newer rustc accepts. We munch the
+    // comma-separated list ourselves rather than rewriting it into `all(...)` tokens
+    // and re-parsing, since `key` (e.g. `os`) isn't the same identifier as the
+    // `target_key` (e.g. `target_os`) it stands for.
+    (
+        @expr
+        target($key:ident = $value:expr)
+    ) => {
+        $crate::cfg_aliases!(@target_key $key = $value)
+    };
+    (
+        @expr
+        target($key:ident = $value:expr, $($rest:tt)+)
+    ) => {
+        $crate::CfgExpr::All(vec![
+            $crate::cfg_aliases!(@target_key $key = $value),
+            $crate::cfg_aliases!(@expr target($($rest)+)),
+        ])
+    };
+
+    // Maps the bare keys accepted by `target(...)` to the `target_*` cfg they stand for.
+    (@target_key os = $value:expr) => { $crate::CfgExpr::KeyValue("target_os".to_string(), ($value).to_string()) };
+    (@target_key arch = $value:expr) => { $crate::CfgExpr::KeyValue("target_arch".to_string(), ($value).to_string()) };
+    (@target_key vendor = $value:expr) => { $crate::CfgExpr::KeyValue("target_vendor".to_string(), ($value).to_string()) };
+    (@target_key family = $value:expr) => { $crate::CfgExpr::KeyValue("target_family".to_string(), ($value).to_string()) };
+    (@target_key env = $value:expr) => { $crate::CfgExpr::KeyValue("target_env".to_string(), ($value).to_string()) };
+    (@target_key abi = $value:expr) => { $crate::CfgExpr::KeyValue("target_abi".to_string(), ($value).to_string()) };
+    (@target_key endian = $value:expr) => { $crate::CfgExpr::KeyValue("target_endian".to_string(), ($value).to_string()) };
+    (@target_key pointer_width = $value:expr) => { $crate::CfgExpr::KeyValue("target_pointer_width".to_string(), ($value).to_string()) };
+    (@target_key feature = $value:expr) => { $crate::CfgExpr::KeyValue("target_feature".to_string(), ($value).to_string()) };
+    // Fallback for any key `target(...)` doesn't recognize. Without this, an
+    // unmatched `@target_key` falls through to the catch-all at the bottom of the
+    // macro, which re-feeds the same tokens back into `cfg_aliases!`'s entrypoint
+    // and recurses until the compiler's recursion limit is hit instead of naming
+    // the bad key.
+    (@target_key $key:ident = $value:expr) => {
+        compile_error!(concat!(
+            "unsupported key `",
+            stringify!($key),
+            "` in target(...); expected one of: os, arch, vendor, family, env, abi, endian, pointer_width, feature",
+        ))
     };
 
-    // `feature = value`: test for a feature.
-    (@parser feature = $value:expr) => {
-        $crate::cfg_aliases!(@cfg_has_feature $value)
+    // `feature = value`: a feature atom.
+    (@expr feature = $value:expr) => {
+        $crate::CfgExpr::KeyValue("feature".to_string(), ($value).to_string())
     };
-    // `param = value`: test for equality.
-    (@parser $key:ident = $value:expr) => {
-        $crate::cfg_aliases!(@cfg_contains $key = $value)
+    // `param = value`: a `key = value` atom.
+    (@expr $key:ident = $value:expr) => {
+        $crate::CfgExpr::KeyValue(stringify!($key).to_string(), ($value).to_string())
     };
     // Parse a lone identifier that might be an alias
-    (@parser $e:ident) => {
+    (@expr $e:ident) => {
         __cfg_aliases_matcher__!($e)
     };
 
-    // Entrypoint that defines the matcher
+    // Shared by `cfg_aliases!` and `cfg_aliases_with!`: builds each alias's
+    // `CfgExpr`, folds it against `$query`, and emits the usual `rustc-cfg` /
+    // `rustc-check-cfg` directives for whichever aliases hold true.
     (
-        @with_dollar[$dol:tt]
+        @emit_aliases[$dol:tt] $query:expr;
         $( $alias:ident : { $($config:tt)* } ),* $(,)?
     ) => {
         // Create a macro that expands other aliases and outputs any non
-        // alias by checking whether that CFG value is set
+        // alias as a plain atom to be resolved by the query
         macro_rules! __cfg_aliases_matcher__ {
             // Parse config expression for the alias
             $(
                 ( $alias ) => {
-                    $crate::cfg_aliases!(@parser $($config)*)
+                    $crate::cfg_aliases!(@expr $($config)*)
                 };
             )*
-            // Anything that doesn't match evaluate the item
+            // Anything that doesn't match is a plain cfg atom
             ( $dol e:ident ) => {
-                $crate::cfg_aliases!(@cfg_is_set $dol e)
+                $crate::CfgExpr::Atom(stringify!($dol e).to_string())
             };
         }
 
         $(
-            if $crate::cfg_aliases!(@parser $($config)*) {
+            if $crate::cfg_aliases!(@expr $($config)*).fold(&$query).unwrap_or(false) {
                 println!("cargo:rustc-cfg={}", stringify!($alias));
             }
         )*
+
+        // Tell Cargo about every alias we might emit so the `unexpected_cfgs` lint
+        // doesn't fire on `#[cfg(some_alias)]` in downstream code. Only do this on
+        // a new-enough Cargo, since older versions reject the `cargo::` directive
+        // syntax outright.
+        if $crate::__private::cargo_supports_check_cfg() {
+            $(
+                println!("cargo::rustc-check-cfg=cfg({})", stringify!($alias));
+            )*
+        }
+    };
+
+    // Entrypoint that defines the matcher, evaluating every alias against the
+    // default, environment-backed query.
+    (
+        @with_dollar[$dol:tt]
+        $( $alias:ident : { $($config:tt)* } ),* $(,)?
+    ) => {
+        $crate::cfg_aliases!(
+            @emit_aliases[$dol] $crate::__private::default_query;
+            $( $alias : { $($config)* } ),*
+        )
+    };
+
+    // Entrypoint for `cfg_aliases_with!`, evaluating every alias against a
+    // caller-provided query instead.
+    (
+        @with_dollar_query[$dol:tt] $query:expr,
+        $( $alias:ident : { $($config:tt)* } ),* $(,)?
+    ) => {
+        $crate::cfg_aliases!(
+            @emit_aliases[$dol] $query;
+            $( $alias : { $($config)* } ),*
+        )
+    };
+
+    (@with_query $query:expr, $($tokens:tt)*) => {
+        $crate::cfg_aliases!(@with_dollar_query[$] $query, $($tokens)*)
+    };
+
+    // Entrypoint for `cfg_eval!`. It runs the exact same `@expr` arms as alias
+    // declaration, but since there are no user-declared aliases in scope, its
+    // `__cfg_aliases_matcher__!` has only the catch-all arm: every bare
+    // identifier becomes a plain atom resolved straight against the environment.
+    (
+        @eval_with_dollar[$dol:tt]
+        $($config:tt)*
+    ) => {
+        {
+            macro_rules! __cfg_aliases_matcher__ {
+                ( $dol e:ident ) => {
+                    $crate::CfgExpr::Atom(stringify!($dol e).to_string())
+                };
+            }
+            $crate::cfg_aliases!(@expr $($config)*)
+                .fold(&$crate::__private::default_query)
+                .unwrap_or(false)
+        }
+    };
+
+    (@eval $($tokens:tt)*) => {
+        $crate::cfg_aliases!(@eval_with_dollar[$] $($tokens)*)
+    };
+
+    // Entrypoint for `cfg_aliases_match!`: `name => { cond => value, ..., _ => default }`.
+    (
+        @match_with_dollar[$dol:tt]
+        $name:ident => { $($body:tt)* }
+    ) => {
+        {
+            macro_rules! __cfg_aliases_matcher__ {
+                ( $dol e:ident ) => {
+                    $crate::CfgExpr::Atom(stringify!($dol e).to_string())
+                };
+            }
+            $crate::cfg_aliases!(@match_clause $name [] [$($body)*]);
+        }
+    };
+
+    (@match $($tokens:tt)*) => {
+        $crate::cfg_aliases!(@match_with_dollar[$] $($tokens)*)
+    };
+
+    // Munches the `cond => value,` arms one token at a time (conditions may
+    // themselves be multi-token, e.g. `all(unix, feature = "x")`, so we can't use
+    // a single `:tt` to capture one), stopping each arm at the first *top-level*
+    // `=>` -- one inside a nested `all(...)`/`any(...)` group is invisible here
+    // since a parenthesized group is a single token tree.
+    (
+        @match_clause
+        $name:ident
+        [$($grouped:tt)*]
+        [=> $value:expr, $($rest:tt)*]
+        $($current:tt)+
+    ) => {
+        $crate::cfg_aliases!(@match_clause $name [$($grouped)* {$($current)+} => $value,] [$($rest)*]);
+    };
+    (
+        @match_clause
+        $name:ident
+        [$($grouped:tt)*]
+        [=> $value:expr]
+        $($current:tt)+
+    ) => {
+        $crate::cfg_aliases!(@match_clause $name [$($grouped)* {$($current)+} => $value,] []);
+    };
+    (
+        @match_clause
+        $name:ident
+        [$($grouped:tt)*]
+        [$tok:tt $($rest:tt)*]
+        $($current:tt)*
+    ) => {
+        $crate::cfg_aliases!(@match_clause $name [$($grouped)*] [$($rest)*] $($current)* $tok);
+    };
+    (
+        @match_clause
+        $name:ident
+        [$($grouped:tt)*]
+        []
+    ) => {
+        $crate::cfg_aliases!(@match_validate $name $($grouped)*);
+        $crate::cfg_aliases!(@match_register $name $($grouped)*);
+        $crate::cfg_aliases!(@match_emit $name $($grouped)*);
+    };
+
+    // The `_` default must be the last arm -- it matches unconditionally, so any
+    // arm after it would be unreachable dead code that silently never fires.
+    (@match_validate $name:ident) => {};
+    (@match_validate $name:ident {_} => $value:expr,) => {};
+    (@match_validate $name:ident {_} => $value:expr, $($rest:tt)+) => {
+        compile_error!(concat!(
+            "the `_` arm in `cfg_aliases_match! { ",
+            stringify!($name),
+            " => { ... } }` must be the last arm -- move it after every condition it's meant to fall back to",
+        ));
+    };
+    (@match_validate $name:ident {$($cond:tt)+} => $value:expr, $($rest:tt)*) => {
+        $crate::cfg_aliases!(@match_validate $name $($rest)*);
+    };
+
+    // Registers every arm's value with Cargo via `rustc-check-cfg` (same
+    // new-enough-Cargo gate as `cfg_aliases!` uses) so `#[cfg(name = "value")]` in
+    // downstream code doesn't trip `unexpected_cfgs`.
+    (
+        @match_register
+        $name:ident
+        $({$($cond:tt)*} => $value:expr),* $(,)?
+    ) => {
+        if $crate::__private::cargo_supports_check_cfg() {
+            println!(
+                "cargo::rustc-check-cfg=cfg({}, values({}))",
+                stringify!($name),
+                [$(stringify!($value)),*].join(", ")
+            );
+        }
+    };
+
+    // Emits a single `rustc-cfg={name}="{value}"` for the first arm that holds
+    // true (or the `_` default, if present). The actual "pick the first true
+    // arm's value" logic lives in `__private::first_match`, a plain function, so
+    // the cascade's exclusivity semantics are unit-testable directly instead of
+    // only being reachable through this `println!`-emitting macro.
+    (
+        @match_emit
+        $name:ident
+        $({$($cond:tt)*} => $value:expr),* $(,)?
+    ) => {
+        if let Some(value) = $crate::__private::first_match(&[
+            $( $crate::cfg_aliases!(@match_cond {$($cond)*} => $value) ),*
+        ]) {
+            println!("cargo:rustc-cfg={}=\"{}\"", stringify!($name), value);
+        }
+    };
+
+    // Builds one `(bool, value)` tuple per arm: `_` is unconditionally true
+    // (it's the fallback), everything else folds its condition against the
+    // default query.
+    (@match_cond {_} => $value:expr) => {
+        (true, $value)
+    };
+    (@match_cond {$($cond:tt)+} => $value:expr) => {
+        (
+            $crate::cfg_aliases!(@expr $($cond)+).fold(&$crate::__private::default_query).unwrap_or(false),
+            $value,
+        )
     };
 
     // Catch all that starts the macro
@@ -261,3 +660,241 @@ macro_rules! cfg_aliases {
         $crate::cfg_aliases!(@with_dollar[$] $($tokens)*)
     }
 }
+
+/// Evaluate a `cfg`-like expression down to a plain `bool`, for build-script logic
+/// that doesn't map onto a single `rustc-cfg` flag (choosing a bindgen path,
+/// picking a vendored source tree, etc.).
+///
+/// This runs through the same parsing and boolean combinators as `cfg_aliases!`
+/// (`all`, `any`, `not`, `feature = "..."`, `version("...")`, `target(...)`), so
+/// nested expressions behave identically. Unlike `cfg_aliases!`, it doesn't know
+/// about any aliases declared elsewhere; a bare identifier is resolved straight
+/// against the environment, exactly as `#[cfg(some_ident)]` would be.
+///
+/// ```rust
+/// # use cfg_aliases::cfg_eval;
+/// if cfg_eval!(all(unix, feature = "foo")) {
+///     // Do stuff related to foo
+/// }
+/// ```
+#[macro_export]
+macro_rules! cfg_eval {
+    ($($tokens:tt)*) => {
+        $crate::cfg_aliases!(@eval $($tokens)*)
+    };
+}
+
+/// Like `cfg_aliases!`, but evaluates every alias against a caller-provided query
+/// instead of reading `CARGO_CFG_*`/`CARGO_FEATURE_*` from the process environment.
+///
+/// `$query` is anything implementing `Fn(&str, Option<&str>) -> bool` (a closure
+/// wrapping, say, a `HashMap` of the cfgs you want to pretend are active). This is
+/// what makes alias evaluation unit-testable without mutating the real
+/// environment, and lets host-side tooling (e.g. something cross-compiling that
+/// has already assembled its own target cfg set) evaluate aliases against cfgs it
+/// put together itself rather than the ones Cargo handed this process.
+///
+/// ```rust
+/// # use cfg_aliases::cfg_aliases_with;
+/// let query = |key: &str, value: Option<&str>| match (key, value) {
+///     ("unix", None) => true,
+///     ("target_os", Some("linux")) => true,
+///     _ => false,
+/// };
+///
+/// cfg_aliases_with! { query,
+///     linux_unix: { all(unix, target_os = "linux") },
+/// }
+/// ```
+#[macro_export]
+macro_rules! cfg_aliases_with {
+    ($($tokens:tt)*) => {
+        $crate::cfg_aliases!(@with_query $($tokens)*)
+    };
+}
+
+/// Like the standard library's `cfg_match!`, but for build scripts: an if/elif
+/// cascade over `cfg`-like conditions that commits to the first one that holds,
+/// emitting a single `cargo:rustc-cfg={name}="{value}"` for it.
+///
+/// Where `cfg_aliases!` gives every alias its own independent flag -- leaving it
+/// up to you to keep them mutually exclusive with `not(any(...))` guards -- this
+/// makes exclusivity structural: at most one branch ever fires, the first in
+/// source order whose condition holds, with an optional `_ => default` arm if you
+/// want a value when nothing else matches (without one, nothing is emitted).
+///
+/// ```rust
+/// # use cfg_aliases::cfg_aliases_match;
+/// cfg_aliases_match! {
+///     backend => {
+///         all(unix, feature = "surfman") => "surfman",
+///         feature = "glutin" => "glutin",
+///         all(windows, feature = "wgl") => "wgl",
+///         _ => "dummy",
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! cfg_aliases_match {
+    ($($tokens:tt)*) => {
+        $crate::cfg_aliases!(@match $($tokens)*)
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::CfgExpr;
+    use std::collections::HashMap;
+
+    // Builds a query closure backed by a `HashMap`, so these tests evaluate
+    // `CfgExpr`/`cfg_aliases_with!` against a synthetic cfg set instead of the real
+    // process environment.
+    fn query_from<'a>(
+        cfgs: &'a HashMap<&'a str, Option<&'a str>>,
+    ) -> impl Fn(&str, Option<&str>) -> bool + 'a {
+        move |key, value| cfgs.get(key).is_some_and(|v| *v == value)
+    }
+
+    #[test]
+    fn fold_atom_and_key_value() {
+        let mut cfgs = HashMap::new();
+        cfgs.insert("unix", None);
+        cfgs.insert("target_os", Some("linux"));
+        let query = query_from(&cfgs);
+
+        assert_eq!(CfgExpr::Atom("unix".to_string()).fold(&query), Some(true));
+        assert_eq!(CfgExpr::Atom("windows".to_string()).fold(&query), Some(false));
+        assert_eq!(
+            CfgExpr::KeyValue("target_os".to_string(), "linux".to_string()).fold(&query),
+            Some(true)
+        );
+        assert_eq!(
+            CfgExpr::KeyValue("target_os".to_string(), "macos".to_string()).fold(&query),
+            Some(false)
+        );
+    }
+
+    #[test]
+    fn fold_all_any_not_nested() {
+        let mut cfgs = HashMap::new();
+        cfgs.insert("unix", None);
+        cfgs.insert("target_os", Some("linux"));
+        let query = query_from(&cfgs);
+
+        let all = CfgExpr::All(vec![
+            CfgExpr::Atom("unix".to_string()),
+            CfgExpr::KeyValue("target_os".to_string(), "linux".to_string()),
+        ]);
+        assert_eq!(all.fold(&query), Some(true));
+
+        let any = CfgExpr::Any(vec![
+            CfgExpr::Atom("windows".to_string()),
+            CfgExpr::KeyValue("target_os".to_string(), "macos".to_string()),
+        ]);
+        assert_eq!(any.fold(&query), Some(false));
+
+        // `not(any(windows, macos))` should be true under this query.
+        let nested = CfgExpr::Not(Box::new(CfgExpr::Any(vec![
+            CfgExpr::Atom("windows".to_string()),
+            CfgExpr::KeyValue("target_os".to_string(), "macos".to_string()),
+        ])));
+        assert_eq!(nested.fold(&query), Some(true));
+
+        let all_of_nested = CfgExpr::All(vec![CfgExpr::Atom("unix".to_string()), nested]);
+        assert_eq!(all_of_nested.fold(&query), Some(true));
+    }
+
+    #[test]
+    fn cfg_aliases_with_evaluates_against_caller_query() {
+        // `cfg_aliases_with!` parses each alias's `{ ... }` body into exactly this
+        // shape of `CfgExpr` and folds it against the caller's query below. Its own
+        // effect (the `println!`) only lands on the *next* compilation, so we pin
+        // down the actual true/false semantics via `CfgExpr::fold` directly, then
+        // smoke-test the real macro invocation for the no-panic/compiles case.
+        let query = |key: &str, value: Option<&str>| matches!((key, value), ("target_os", Some("linux")));
+
+        let linux = CfgExpr::KeyValue("target_os".to_string(), "linux".to_string());
+        assert_eq!(linux.fold(&query), Some(true));
+
+        let not_windows = CfgExpr::Not(Box::new(CfgExpr::KeyValue(
+            "target_os".to_string(),
+            "windows".to_string(),
+        )));
+        assert_eq!(not_windows.fold(&query), Some(true));
+
+        crate::cfg_aliases_with! { query,
+            linux: { target_os = "linux" },
+            not_windows: { not(target_os = "windows") },
+        }
+    }
+
+    #[test]
+    fn cfg_eval_reads_the_process_environment() {
+        // `cfg_eval!` has no caller-query variant -- it always folds against
+        // `__private::default_query`, i.e. the real environment -- so this test
+        // sets env vars rather than using a `HashMap`-backed query.
+        std::env::set_var("CARGO_CFG_CFG_ALIASES_TEST_ATOM", "");
+        assert!(crate::cfg_eval!(cfg_aliases_test_atom));
+        std::env::remove_var("CARGO_CFG_CFG_ALIASES_TEST_ATOM");
+        assert!(!crate::cfg_eval!(cfg_aliases_test_atom));
+    }
+
+    #[test]
+    fn cfg_eval_supports_version_predicate_via_real_rustc() {
+        // `version(...)` shells out to the real compiler rather than reading a cfg
+        // var, so it can't be faked through a `HashMap` query -- check it against a
+        // version old enough, and new enough, to be stable regardless of the rustc
+        // running these tests.
+        assert!(crate::cfg_eval!(version("1.0")));
+        assert!(!crate::cfg_eval!(version("9999.0")));
+    }
+
+    #[test]
+    fn cfg_eval_supports_target_predicate_via_env() {
+        std::env::set_var("CARGO_CFG_TARGET_OS", "linux");
+        std::env::set_var("CARGO_CFG_TARGET_ARCH", "x86_64");
+        assert!(crate::cfg_eval!(target(os = "linux", arch = "x86_64")));
+        assert!(!crate::cfg_eval!(target(os = "macos")));
+        std::env::remove_var("CARGO_CFG_TARGET_OS");
+        std::env::remove_var("CARGO_CFG_TARGET_ARCH");
+    }
+
+    #[test]
+    fn first_match_picks_the_first_true_arm() {
+        assert_eq!(
+            crate::__private::first_match(&[(false, "a"), (true, "b"), (true, "c")]),
+            Some("b")
+        );
+    }
+
+    #[test]
+    fn first_match_falls_back_to_a_trailing_default() {
+        // `cfg_aliases_match!` represents its `_` arm as an unconditional `true`,
+        // so it only wins here because nothing earlier in the list matched.
+        assert_eq!(
+            crate::__private::first_match(&[(false, "a"), (false, "b"), (true, "dummy")]),
+            Some("dummy")
+        );
+    }
+
+    #[test]
+    fn first_match_is_none_when_nothing_matches() {
+        assert_eq!(crate::__private::first_match::<&str>(&[(false, "a"), (false, "b")]), None);
+    }
+
+    #[test]
+    fn cfg_aliases_match_compiles_and_runs_with_a_trailing_default() {
+        // `cfg_aliases_match!` itself only ever emits `println!` -- its effect is
+        // on the *next* compilation, not this one -- so this is a smoke test for
+        // the no-panic/compiles case; `first_match`'s tests above pin down the
+        // actual first-true-wins semantics it relies on.
+        std::env::set_var("CARGO_CFG_CFG_ALIASES_MATCH_TEST", "");
+        crate::cfg_aliases_match! {
+            backend => {
+                cfg_aliases_match_test => "matched",
+                _ => "dummy",
+            }
+        }
+        std::env::remove_var("CARGO_CFG_CFG_ALIASES_MATCH_TEST");
+    }
+}